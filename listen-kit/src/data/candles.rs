@@ -0,0 +1,394 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use futures_util::StreamExt;
+use raydium_library::amm;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use log::warn;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::program_pack::Pack;
+use tokio::sync::RwLock;
+
+use super::Candlestick;
+
+/// A single decoded swap against an AMM pool.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Trade {
+    pub price: f64,
+    pub base_size: f64,
+    pub quote_size: f64,
+    pub block_time: i64,
+    pub slot: u64,
+}
+
+/// All intervals the local indexer maintains candles for, matching what
+/// `fetch_candlesticks` exposes over the API.
+pub const SUPPORTED_INTERVALS: &[(&str, i64)] = &[
+    ("15s", 15),
+    ("30s", 30),
+    ("1m", 60),
+    ("5m", 300),
+    ("15m", 900),
+    ("30m", 1800),
+    ("1h", 3600),
+    ("4h", 14400),
+    ("1d", 86400),
+];
+
+pub fn interval_secs(interval: &str) -> Result<i64> {
+    SUPPORTED_INTERVALS
+        .iter()
+        .find(|(name, _)| *name == interval)
+        .map(|(_, secs)| *secs)
+        .ok_or_else(|| anyhow!("Invalid interval: {}", interval))
+}
+
+/// Raw trades and rolled-up candles for a single mint, keyed by block time
+/// rather than slot so backfills never leave gaps when slots are skipped.
+#[derive(Debug, Default)]
+struct MintHistory {
+    trades: BTreeMap<i64, Trade>,
+    candles: std::collections::HashMap<&'static str, BTreeMap<i64, Candlestick>>,
+}
+
+/// In-memory local OHLCV index, fed by `index_pool_swaps` and read by
+/// `fetch_candlesticks_local`. Serves as a fallback source when
+/// `https://api.listen-rs.com` is unreachable.
+#[derive(Debug, Default, Clone)]
+pub struct CandleStore {
+    inner: Arc<RwLock<std::collections::HashMap<String, MintHistory>>>,
+}
+
+impl CandleStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Persists a raw trade and rolls it into every supported interval's
+    /// candle bucket. Call this once per decoded swap.
+    pub async fn record_trade(&self, mint: &str, trade: Trade) {
+        let mut guard = self.inner.write().await;
+        let history = guard.entry(mint.to_string()).or_default();
+        history.trades.insert(trade.block_time, trade);
+
+        for (name, secs) in SUPPORTED_INTERVALS {
+            let bucket = trade.block_time / secs;
+            let candles = history.candles.entry(name).or_default();
+            candles
+                .entry(bucket)
+                .and_modify(|c| {
+                    c.high = c.high.max(trade.price);
+                    c.low = c.low.min(trade.price);
+                    c.close = trade.price;
+                    c.volume += trade.base_size;
+                })
+                .or_insert(Candlestick {
+                    timestamp: (bucket * secs) as u64,
+                    open: trade.price,
+                    high: trade.price,
+                    low: trade.price,
+                    close: trade.price,
+                    volume: trade.base_size,
+                });
+        }
+    }
+
+    /// Serves the same shape `fetch_candlesticks` returns from the remote
+    /// API, most recent candle last.
+    pub async fn candlesticks(&self, mint: &str, interval: &str, limit: usize) -> Result<Vec<Candlestick>> {
+        interval_secs(interval)?;
+        let guard = self.inner.read().await;
+        let Some(history) = guard.get(mint) else {
+            return Ok(vec![]);
+        };
+        let Some(candles) = history.candles.get(interval) else {
+            return Ok(vec![]);
+        };
+        Ok(candles
+            .values()
+            .rev()
+            .take(limit)
+            .rev()
+            .cloned()
+            .collect())
+    }
+
+    /// Mints with at least one indexed trade, for building ticker listings.
+    pub async fn known_mints(&self) -> Vec<String> {
+        self.inner.read().await.keys().cloned().collect()
+    }
+}
+
+/// Decodes a Raydium AMM pool account update into a trade, if the update
+/// reflects a swap (a change in both base and quote reserves) rather than a
+/// deposit/withdraw or unrelated write.
+fn decode_swap(prev_base: f64, prev_quote: f64, base: f64, quote: f64, block_time: i64, slot: u64) -> Option<Trade> {
+    let base_size = base - prev_base;
+    let quote_size = quote - prev_quote;
+    if base_size == 0.0 || quote_size == 0.0 || base_size.signum() == quote_size.signum() {
+        return None;
+    }
+    Some(Trade {
+        price: (quote_size / base_size).abs(),
+        base_size: base_size.abs(),
+        quote_size: quote_size.abs(),
+        block_time,
+        slot,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VaultSide {
+    Base,
+    Quote,
+}
+
+/// Subscribes to a Raydium AMM pool's coin/pc vaults, decodes each vault's
+/// SPL token balance, and feeds every swap (a reserve move seen on both
+/// vaults since the last trade) into `store`, keyed by `mint`. Runs until
+/// both subscriptions end; intended to be spawned alongside
+/// `track_lamports_balance`-style tasks.
+pub async fn index_pool_swaps(
+    rpc_client: &RpcClient,
+    pubsub_client: &PubsubClient,
+    amm_keys: &amm::utils::AmmKeys,
+    mint: &str,
+    store: CandleStore,
+) -> Result<()> {
+    let cfg = RpcAccountInfoConfig {
+        commitment: Some(CommitmentConfig::processed()),
+        encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+        ..Default::default()
+    };
+    let (base_stream, base_unsub) = pubsub_client
+        .account_subscribe(&amm_keys.amm_coin_vault, Some(cfg.clone()))
+        .await?;
+    let (quote_stream, quote_unsub) = pubsub_client
+        .account_subscribe(&amm_keys.amm_pc_vault, Some(cfg))
+        .await?;
+
+    // Token accounts only carry raw integer amounts; fetch each side's
+    // decimals once up front so prices/sizes reflect UI units, matching
+    // `track_token_balance`/`track_balances_grpc`.
+    let base_decimals = rpc_client
+        .get_token_supply(&amm_keys.amm_coin_mint)
+        .await
+        .map(|supply| supply.decimals)
+        .unwrap_or(9);
+    let quote_decimals = rpc_client
+        .get_token_supply(&amm_keys.amm_pc_mint)
+        .await
+        .map(|supply| supply.decimals)
+        .unwrap_or(9);
+
+    let mut merged = futures_util::stream::select(
+        base_stream.map(|update| (VaultSide::Base, update)),
+        quote_stream.map(|update| (VaultSide::Quote, update)),
+    );
+
+    let mut base = 0.0;
+    let mut quote = 0.0;
+    let mut prev_base = 0.0;
+    let mut prev_quote = 0.0;
+    let mut base_moved = false;
+    let mut quote_moved = false;
+    let mut initialized = false;
+
+    while let Some((side, update)) = merged.next().await {
+        let Some(account) = update.value.data.decode() else {
+            continue;
+        };
+        let Ok(token_account) = spl_token::state::Account::unpack(&account.data) else {
+            continue;
+        };
+        let slot = update.context.slot;
+
+        match side {
+            VaultSide::Base => {
+                base = token_account.amount as f64 / 10f64.powi(base_decimals as i32);
+                base_moved = true;
+            }
+            VaultSide::Quote => {
+                quote = token_account.amount as f64 / 10f64.powi(quote_decimals as i32);
+                quote_moved = true;
+            }
+        }
+
+        if !initialized {
+            prev_base = base;
+            prev_quote = quote;
+            initialized = true;
+            base_moved = false;
+            quote_moved = false;
+            continue;
+        }
+
+        // A swap moves both vaults; wait until we've seen a write on each
+        // side since the last recorded trade before decoding one.
+        if !(base_moved && quote_moved) {
+            continue;
+        }
+
+        let block_time = match rpc_client.get_block_time(slot).await {
+            Ok(block_time) => block_time,
+            Err(e) => {
+                warn!("failed to fetch block time for slot {slot}, skipping trade: {e}");
+                prev_base = base;
+                prev_quote = quote;
+                base_moved = false;
+                quote_moved = false;
+                continue;
+            }
+        };
+        if let Some(trade) = decode_swap(prev_base, prev_quote, base, quote, block_time, slot) {
+            store.record_trade(mint, trade).await;
+        }
+        prev_base = base;
+        prev_quote = quote;
+        base_moved = false;
+        quote_moved = false;
+    }
+
+    base_unsub().await;
+    quote_unsub().await;
+    Ok(())
+}
+
+/// Local fallback for `fetch_candlesticks`: takes the same mint, interval
+/// and limit arguments plus the store to read from, and returns the same
+/// candle shape, so the tool can swap sources without the caller noticing.
+pub async fn fetch_candlesticks_local(
+    store: &CandleStore,
+    mint: &str,
+    interval: &str,
+    limit: Option<usize>,
+) -> Result<Vec<Candlestick>> {
+    store
+        .candlesticks(mint, interval, limit.unwrap_or(200))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn record_trade_opens_highs_lows_and_accumulates_volume() {
+        let store = CandleStore::new();
+        let mint = "mint1";
+        store
+            .record_trade(
+                mint,
+                Trade {
+                    price: 1.0,
+                    base_size: 10.0,
+                    quote_size: 10.0,
+                    block_time: 0,
+                    slot: 1,
+                },
+            )
+            .await;
+        store
+            .record_trade(
+                mint,
+                Trade {
+                    price: 1.5,
+                    base_size: 5.0,
+                    quote_size: 7.5,
+                    block_time: 5,
+                    slot: 2,
+                },
+            )
+            .await;
+        store
+            .record_trade(
+                mint,
+                Trade {
+                    price: 0.8,
+                    base_size: 2.0,
+                    quote_size: 1.6,
+                    block_time: 10,
+                    slot: 3,
+                },
+            )
+            .await;
+
+        let candles = store.candlesticks(mint, "15s", 10).await.unwrap();
+        assert_eq!(candles.len(), 1);
+        let candle = &candles[0];
+        assert_eq!(candle.open, 1.0);
+        assert_eq!(candle.high, 1.5);
+        assert_eq!(candle.low, 0.8);
+        assert_eq!(candle.close, 0.8);
+        assert_eq!(candle.volume, 17.0);
+    }
+
+    #[tokio::test]
+    async fn trades_in_different_buckets_produce_separate_candles() {
+        let store = CandleStore::new();
+        let mint = "mint1";
+        store
+            .record_trade(
+                mint,
+                Trade {
+                    price: 1.0,
+                    base_size: 1.0,
+                    quote_size: 1.0,
+                    block_time: 0,
+                    slot: 1,
+                },
+            )
+            .await;
+        store
+            .record_trade(
+                mint,
+                Trade {
+                    price: 2.0,
+                    base_size: 1.0,
+                    quote_size: 2.0,
+                    block_time: 20,
+                    slot: 2,
+                },
+            )
+            .await;
+
+        let candles = store.candlesticks(mint, "15s", 10).await.unwrap();
+        assert_eq!(candles.len(), 2);
+    }
+
+    #[test]
+    fn decode_swap_requires_opposite_signed_reserve_moves() {
+        assert!(decode_swap(100.0, 100.0, 110.0, 90.0, 0, 1).is_some());
+        assert!(decode_swap(100.0, 100.0, 110.0, 110.0, 0, 1).is_none());
+        assert!(decode_swap(100.0, 100.0, 100.0, 100.0, 0, 1).is_none());
+    }
+
+    #[tokio::test]
+    async fn candlesticks_rejects_unsupported_interval() {
+        let store = CandleStore::new();
+        assert!(store.candlesticks("mint1", "2m", 10).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn known_mints_lists_mints_with_at_least_one_trade() {
+        let store = CandleStore::new();
+        assert!(store.known_mints().await.is_empty());
+        store
+            .record_trade(
+                "mint1",
+                Trade {
+                    price: 1.0,
+                    base_size: 1.0,
+                    quote_size: 1.0,
+                    block_time: 0,
+                    slot: 1,
+                },
+            )
+            .await;
+        assert_eq!(store.known_mints().await, vec!["mint1".to_string()]);
+    }
+}
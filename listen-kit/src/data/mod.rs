@@ -2,7 +2,10 @@ use anyhow::{anyhow, Result};
 use rig_tool_macro::tool;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+mod candles;
+pub use candles::{index_pool_swaps, CandleStore, Trade};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Candlestick {
     pub timestamp: u64,
     pub open: f64,
@@ -116,6 +119,20 @@ pub async fn fetch_candlesticks(
         _ => return Err(anyhow!("Invalid interval: {}", interval)),
     }
 
+    match fetch_candlesticks_remote(&mint, &interval, limit).await {
+        Ok(candlesticks) => Ok(candlesticks),
+        Err(e) => {
+            log::warn!("remote candlesticks fetch failed, falling back to local index: {e}");
+            candles::fetch_candlesticks_local(local_candle_store(), &mint, &interval, limit).await
+        }
+    }
+}
+
+async fn fetch_candlesticks_remote(
+    mint: &str,
+    interval: &str,
+    limit: Option<usize>,
+) -> Result<Vec<Candlestick>> {
     let mut url = format!(
         "{}/candlesticks?mint={}&interval={}",
         API_BASE, mint, interval
@@ -137,6 +154,14 @@ pub async fn fetch_candlesticks(
     Ok(candlesticks)
 }
 
+/// Process-wide local candle index, fed by `candles::index_pool_swaps` and
+/// used as the fallback source when the remote API is unreachable.
+static LOCAL_CANDLE_STORE: std::sync::OnceLock<CandleStore> = std::sync::OnceLock::new();
+
+pub fn local_candle_store() -> &'static CandleStore {
+    LOCAL_CANDLE_STORE.get_or_init(CandleStore::new)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
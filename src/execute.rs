@@ -0,0 +1,376 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures_util::StreamExt;
+use log::{info, warn};
+use raydium_library::amm;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+
+use crate::provider::Provider;
+use crate::rate::LatestRate;
+use crate::seller;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Static sl/tp levels plus a trailing stop layered on top, all evaluated
+/// against `peak_ratio`/`current_ratio` (pool-implied value of `token_balance`
+/// divided by `lamports_in`), with an additional USD-denominated TP/SL layer
+/// evaluated alongside them via `rate`.
+pub struct Executor {
+    pub amm_keys: amm::utils::AmmKeys,
+    pub funder: Keypair,
+    pub lamports_in: u64,
+    pub token_balance: u64,
+
+    pub sl_levels: Vec<f64>,
+    pub sl_amounts: Vec<f64>,
+    pub sl_reached: Vec<bool>,
+
+    pub tp_levels: Vec<f64>,
+    pub tp_amounts: Vec<f64>,
+    pub tp_reached: Vec<bool>,
+
+    /// Ratio (current value / lamports_in) above which trailing starts
+    /// ratcheting the stop up behind the peak. 0.0 disables trailing.
+    pub activation_ratio: f64,
+    /// Fraction to give back from the peak before trailing fires, e.g. 0.2
+    /// means sell once price drops 20% off the peak.
+    pub trail_pct: f64,
+
+    /// USD PnL thresholds, e.g. 5000.0 for "sell 50% at +$5k PnL".
+    pub usd_tp_levels: Vec<f64>,
+    pub usd_tp_amounts: Vec<f64>,
+    pub usd_tp_reached: Vec<bool>,
+
+    /// USD PnL thresholds on the downside, evaluated the same way as
+    /// `usd_tp_levels` but firing when PnL drops to or below the level.
+    pub usd_sl_levels: Vec<f64>,
+    pub usd_sl_amounts: Vec<f64>,
+    pub usd_sl_reached: Vec<bool>,
+
+    /// SOL/USD source backing the USD thresholds.
+    pub rate: Arc<dyn LatestRate>,
+    /// USD thresholds are skipped once the rate is older than this; ratio
+    /// thresholds still apply.
+    pub rate_staleness: Duration,
+}
+
+/// Outcome of feeding one price update into the trailing-stop state machine.
+#[derive(Debug, PartialEq)]
+pub enum TrailingAction {
+    /// Trailing hasn't armed yet, or armed but the ratchet hasn't tripped.
+    Hold,
+    /// `current_ratio` dropped `trail_pct` below `peak_ratio`; sell.
+    Sell,
+}
+
+/// Runtime state for the trailing stop, persisted across price updates in
+/// `Executor::execute`.
+#[derive(Debug, Clone, Copy)]
+pub struct TrailingState {
+    pub peak_ratio: f64,
+    pub active: bool,
+}
+
+impl Default for TrailingState {
+    fn default() -> Self {
+        Self {
+            peak_ratio: 1.0,
+            active: false,
+        }
+    }
+}
+
+impl Executor {
+    /// Ratchets `peak_ratio` up, arms trailing once `activation_ratio` is
+    /// crossed, and reports whether the trail has now tripped.
+    ///
+    /// A flat or declining price never trips this before `activation_ratio`
+    /// is reached, since `peak_ratio` starts at 1.0 (breakeven) and only ever
+    /// moves up.
+    pub fn update_trailing_stop(
+        &self,
+        state: &mut TrailingState,
+        current_ratio: f64,
+    ) -> TrailingAction {
+        if current_ratio > state.peak_ratio {
+            state.peak_ratio = current_ratio;
+        }
+        if !state.active {
+            if state.peak_ratio >= self.activation_ratio {
+                state.active = true;
+            } else {
+                return TrailingAction::Hold;
+            }
+        }
+        if current_ratio <= state.peak_ratio * (1.0 - self.trail_pct) {
+            TrailingAction::Sell
+        } else {
+            TrailingAction::Hold
+        }
+    }
+
+    /// The stop ratio actually enforced for `sl_levels[i]`, ratcheted up to
+    /// breakeven (1.0) once the first `tp_levels` entry has been reached.
+    fn effective_sl_ratio(&self, level: f64, breakeven_armed: bool) -> f64 {
+        if breakeven_armed {
+            level.max(1.0)
+        } else {
+            level
+        }
+    }
+
+    /// PnL in USD implied by `current_ratio`, using `sol_usd` to convert the
+    /// lamports gained or lost relative to `lamports_in`.
+    fn usd_pnl(&self, current_ratio: f64, sol_usd: f64) -> f64 {
+        let lamports_pnl = (current_ratio - 1.0) * self.lamports_in as f64;
+        (lamports_pnl / LAMPORTS_PER_SOL) * sol_usd
+    }
+
+    /// Clamps a ladder rung's configured sell amount to what's actually left
+    /// of `token_balance`. The sl/tp/usd ladders are configured independently
+    /// and can overlap (e.g. a pump crossing a ratio `tp_levels` entry and a
+    /// USD PnL threshold in the same tick), so every fire has to be sized
+    /// against the shared remaining balance, not just its own ladder.
+    fn clamp_to_remaining(requested: f64, remaining: f64) -> f64 {
+        requested.min(remaining).max(0.0)
+    }
+
+    pub async fn execute(
+        &self,
+        provider: &Provider,
+        pubsub_client: &PubsubClient,
+        amm_pool: &Pubkey,
+    ) -> Result<()> {
+        let (mut stream, unsub) = pubsub_client
+            .account_subscribe(
+                amm_pool,
+                Some(RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        let mut sl_reached = self.sl_reached.clone();
+        let mut tp_reached = self.tp_reached.clone();
+        let mut usd_tp_reached = self.usd_tp_reached.clone();
+        let mut usd_sl_reached = self.usd_sl_reached.clone();
+        let mut trailing_state = TrailingState::default();
+        let mut remaining = self.token_balance as f64;
+
+        while let Some(_update) = stream.next().await {
+            let current_ratio =
+                seller::pool_implied_ratio(provider, &self.amm_keys, self.lamports_in).await?;
+            let breakeven_armed = tp_reached.first().copied().unwrap_or(false);
+
+            for (i, level) in self.sl_levels.iter().enumerate() {
+                if sl_reached[i] {
+                    continue;
+                }
+                if current_ratio <= self.effective_sl_ratio(*level, breakeven_armed) {
+                    sl_reached[i] = true;
+                    let amount = Self::clamp_to_remaining(self.sl_amounts[i], remaining);
+                    remaining -= amount;
+                    info!("sl[{i}] @ {level} hit, ratio={current_ratio}, selling {amount}");
+                    seller::sell(provider, &self.funder, amm_pool, amount).await?;
+                }
+            }
+
+            for (i, level) in self.tp_levels.iter().enumerate() {
+                if tp_reached[i] {
+                    continue;
+                }
+                if current_ratio >= *level {
+                    tp_reached[i] = true;
+                    let amount = Self::clamp_to_remaining(self.tp_amounts[i], remaining);
+                    remaining -= amount;
+                    info!("tp[{i}] @ {level} hit, ratio={current_ratio}, selling {amount}");
+                    seller::sell(provider, &self.funder, amm_pool, amount).await?;
+                }
+            }
+
+            let rate = self.rate.latest().await;
+            if rate.as_of.elapsed() <= self.rate_staleness {
+                let usd_pnl = self.usd_pnl(current_ratio, rate.sol_usd);
+
+                for (i, level) in self.usd_tp_levels.iter().enumerate() {
+                    if usd_tp_reached[i] {
+                        continue;
+                    }
+                    if usd_pnl >= *level {
+                        usd_tp_reached[i] = true;
+                        let amount = Self::clamp_to_remaining(self.usd_tp_amounts[i], remaining);
+                        remaining -= amount;
+                        info!(
+                            "usd tp[{i}] @ ${level} hit, pnl=${usd_pnl} (ratio={current_ratio}), selling {amount}"
+                        );
+                        seller::sell(provider, &self.funder, amm_pool, amount).await?;
+                    }
+                }
+
+                for (i, level) in self.usd_sl_levels.iter().enumerate() {
+                    if usd_sl_reached[i] {
+                        continue;
+                    }
+                    if usd_pnl <= *level {
+                        usd_sl_reached[i] = true;
+                        let amount = Self::clamp_to_remaining(self.usd_sl_amounts[i], remaining);
+                        remaining -= amount;
+                        info!(
+                            "usd sl[{i}] @ ${level} hit, pnl=${usd_pnl} (ratio={current_ratio}), selling {amount}"
+                        );
+                        seller::sell(provider, &self.funder, amm_pool, amount).await?;
+                    }
+                }
+            } else {
+                warn!(
+                    "sol/usd rate is {}s stale, evaluating ratio thresholds only",
+                    rate.as_of.elapsed().as_secs()
+                );
+            }
+
+            if self.activation_ratio > 0.0 {
+                if let TrailingAction::Sell =
+                    self.update_trailing_stop(&mut trailing_state, current_ratio)
+                {
+                    info!(
+                        "trailing stop hit, peak={}, ratio={current_ratio}, selling remainder {remaining}",
+                        trailing_state.peak_ratio
+                    );
+                    seller::sell(provider, &self.funder, amm_pool, remaining).await?;
+                    remaining = 0.0;
+                    break;
+                }
+            }
+
+            if remaining <= 0.0 {
+                break;
+            }
+        }
+
+        if remaining > 0.0 {
+            warn!("price stream ended with {remaining} unsold");
+        }
+
+        unsub().await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn executor(activation_ratio: f64, trail_pct: f64) -> Executor {
+        Executor {
+            amm_keys: unsafe { std::mem::zeroed() },
+            funder: Keypair::new(),
+            lamports_in: 1_000_000,
+            token_balance: 1_000,
+            sl_levels: vec![0.7, 0.5],
+            sl_amounts: vec![500.0, 500.0],
+            sl_reached: vec![false, false],
+            tp_levels: vec![1.5, 2.0],
+            tp_amounts: vec![400.0, 600.0],
+            tp_reached: vec![false, false],
+            activation_ratio,
+            trail_pct,
+            usd_tp_levels: vec![],
+            usd_tp_amounts: vec![],
+            usd_tp_reached: vec![],
+            usd_sl_levels: vec![],
+            usd_sl_amounts: vec![],
+            usd_sl_reached: vec![],
+            rate: std::sync::Arc::new(crate::rate::FixedRate::new(150.0)),
+            rate_staleness: Duration::from_secs(30),
+        }
+    }
+
+    #[test]
+    fn trailing_stays_dormant_on_flat_or_declining_price() {
+        let executor = executor(1.5, 0.2);
+        let mut state = TrailingState::default();
+        for ratio in [1.0, 0.9, 0.95, 0.8] {
+            assert_eq!(
+                executor.update_trailing_stop(&mut state, ratio),
+                TrailingAction::Hold
+            );
+        }
+        assert!(!state.active);
+        assert_eq!(state.peak_ratio, 1.0);
+    }
+
+    #[test]
+    fn trailing_arms_at_activation_and_fires_on_pullback() {
+        let executor = executor(1.5, 0.2);
+        let mut state = TrailingState::default();
+
+        assert_eq!(
+            executor.update_trailing_stop(&mut state, 1.4),
+            TrailingAction::Hold
+        );
+        assert!(!state.active);
+
+        assert_eq!(
+            executor.update_trailing_stop(&mut state, 2.0),
+            TrailingAction::Hold
+        );
+        assert!(state.active);
+        assert_eq!(state.peak_ratio, 2.0);
+
+        assert_eq!(
+            executor.update_trailing_stop(&mut state, 1.7),
+            TrailingAction::Hold
+        );
+
+        assert_eq!(
+            executor.update_trailing_stop(&mut state, 1.6),
+            TrailingAction::Sell
+        );
+    }
+
+    #[test]
+    fn peak_ratio_only_ever_ratchets_up() {
+        let executor = executor(1.2, 0.1);
+        let mut state = TrailingState::default();
+        executor.update_trailing_stop(&mut state, 1.3);
+        assert_eq!(state.peak_ratio, 1.3);
+        executor.update_trailing_stop(&mut state, 1.25);
+        assert_eq!(state.peak_ratio, 1.3);
+        executor.update_trailing_stop(&mut state, 1.5);
+        assert_eq!(state.peak_ratio, 1.5);
+    }
+
+    #[test]
+    fn breakeven_ratchet_raises_effective_stop_once_first_tp_hit() {
+        let executor = executor(10.0, 0.5);
+        assert_eq!(executor.effective_sl_ratio(0.7, false), 0.7);
+        assert_eq!(executor.effective_sl_ratio(0.7, true), 1.0);
+    }
+
+    #[test]
+    fn usd_pnl_converts_ratio_gain_at_the_given_sol_usd_rate() {
+        let executor = executor(10.0, 0.5);
+        // lamports_in = 1_000_000, ratio 1.5 -> +500_000 lamports gained,
+        // i.e. 0.0005 SOL, at $150/SOL that's $0.075.
+        assert_eq!(executor.usd_pnl(1.5, 150.0), 0.075);
+        assert_eq!(executor.usd_pnl(1.0, 150.0), 0.0);
+        assert!(executor.usd_pnl(0.5, 150.0) < 0.0);
+    }
+
+    #[test]
+    fn clamp_to_remaining_never_sells_more_than_is_left() {
+        assert_eq!(Executor::clamp_to_remaining(400.0, 1000.0), 400.0);
+        assert_eq!(Executor::clamp_to_remaining(400.0, 100.0), 100.0);
+        assert_eq!(Executor::clamp_to_remaining(400.0, 0.0), 0.0);
+        assert_eq!(Executor::clamp_to_remaining(400.0, -50.0), 0.0);
+    }
+}
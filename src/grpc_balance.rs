@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use futures_util::StreamExt;
+use log::warn;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::geyser::{
+    subscribe_update::UpdateOneof, SubscribeRequest, SubscribeRequestFilterAccounts,
+};
+
+use crate::seller_service::BalanceContext;
+use crate::util::env;
+
+/// Opens a Yellowstone/Geyser gRPC stream scoped to `funder` and its SPL
+/// token ATAs for `mints`, feeding both `lamports` and `token_balances` on
+/// every real account write. Lower latency than the `account_subscribe`
+/// websocket path since Geyser pushes straight from validator memory, and
+/// the accounts filter means we never see unrelated slot/vote noise.
+pub async fn track_balances_grpc(
+    ctx: Arc<BalanceContext>,
+    funder: Pubkey,
+    mints: Vec<Pubkey>,
+) -> Result<()> {
+    let mut client = GeyserGrpcClient::connect(
+        env("GEYSER_GRPC_URL"),
+        std::env::var("GEYSER_GRPC_TOKEN").ok(),
+        None,
+    )
+    .map_err(|e| anyhow!("connect to geyser: {e}"))?;
+
+    let atas: HashMap<String, Pubkey> = mints
+        .iter()
+        .map(|mint| {
+            (
+                spl_associated_token_account::get_associated_token_address(&funder, mint)
+                    .to_string(),
+                *mint,
+            )
+        })
+        .collect();
+
+    // Token accounts only carry raw integer amounts; fetch each mint's
+    // decimals once up front so we can store the same UI-amount unit the
+    // pubsub-backed `track_token_balance` does.
+    let rpc_client = RpcClient::new(env("RPC_URL"));
+    let mut decimals_by_mint = HashMap::new();
+    for mint in &mints {
+        let decimals = rpc_client
+            .get_token_supply(mint)
+            .await
+            .map(|supply| supply.decimals)
+            .unwrap_or(9);
+        decimals_by_mint.insert(*mint, decimals);
+    }
+
+    let mut accounts = HashMap::new();
+    accounts.insert(
+        "funder".to_string(),
+        SubscribeRequestFilterAccounts {
+            account: std::iter::once(funder.to_string())
+                .chain(atas.keys().cloned())
+                .collect(),
+            owner: vec![],
+            filters: vec![],
+            // Only react to writes that land in a transaction, so the
+            // initial account snapshot push doesn't count as a balance change.
+            nonempty_txn_signature: Some(true),
+        },
+    );
+
+    let (_sink, mut stream) = client
+        .subscribe_with_request(Some(SubscribeRequest {
+            accounts,
+            ..Default::default()
+        }))
+        .await
+        .map_err(|e| anyhow!("subscribe: {e}"))?;
+
+    while let Some(update) = stream.next().await {
+        let update = match update {
+            Ok(update) => update,
+            Err(e) => {
+                warn!("geyser stream error: {e}");
+                continue;
+            }
+        };
+        let Some(UpdateOneof::Account(account_update)) = update.update_oneof else {
+            continue;
+        };
+        let Some(account) = account_update.account else {
+            continue;
+        };
+        let pubkey = bs58::encode(&account.pubkey).into_string();
+
+        if pubkey == funder.to_string() {
+            *ctx.lamports.write().await = account.lamports;
+            continue;
+        }
+
+        let Some(mint) = atas.get(&pubkey) else {
+            continue;
+        };
+        let Ok(token_account) = spl_token::state::Account::unpack(&account.data) else {
+            continue;
+        };
+        let decimals = decimals_by_mint.get(mint).copied().unwrap_or(9);
+        let ui_amount = token_account.amount as f64 / 10f64.powi(decimals as i32);
+        ctx.token_balances
+            .write()
+            .await
+            .insert(mint.to_string(), ui_amount);
+    }
+
+    Ok(())
+}
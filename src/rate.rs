@@ -0,0 +1,129 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use log::warn;
+use tokio::sync::RwLock;
+use tokio_tungstenite::tungstenite::Message;
+
+/// A SOL/USD quote and when it was observed, used to evaluate USD-denominated
+/// TP/SL thresholds and to detect staleness.
+#[derive(Debug, Clone, Copy)]
+pub struct Rate {
+    pub sol_usd: f64,
+    pub as_of: Instant,
+}
+
+/// Source of the current SOL/USD rate. `FixedRate` is the default for
+/// environments without a ticker feed; `StreamingRate` backs it with a live
+/// exchange websocket.
+#[async_trait]
+pub trait LatestRate: Send + Sync {
+    async fn latest(&self) -> Rate;
+}
+
+/// A rate that never changes, good enough for ratio-only trading or tests.
+pub struct FixedRate(pub Rate);
+
+impl FixedRate {
+    pub fn new(sol_usd: f64) -> Self {
+        Self(Rate {
+            sol_usd,
+            as_of: Instant::now(),
+        })
+    }
+}
+
+#[async_trait]
+impl LatestRate for FixedRate {
+    async fn latest(&self) -> Rate {
+        self.0
+    }
+}
+
+/// Maintains the latest SOL/USD price from a Coinbase/Kraken-style ticker
+/// websocket, reconnecting whenever the connection drops.
+pub struct StreamingRate {
+    inner: Arc<RwLock<Rate>>,
+}
+
+impl StreamingRate {
+    /// Spawns the background reconnect loop and returns immediately; `latest`
+    /// reads whatever has been observed so far (sol_usd = 0.0 until the first
+    /// tick arrives).
+    pub fn spawn(ws_url: String) -> Self {
+        let inner = Arc::new(RwLock::new(Rate {
+            sol_usd: 0.0,
+            as_of: Instant::now(),
+        }));
+        let task_inner = inner.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = Self::run(&ws_url, task_inner.clone()).await {
+                    warn!("sol/usd rate stream disconnected, reconnecting: {e}");
+                }
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        });
+        Self { inner }
+    }
+
+    async fn run(ws_url: &str, inner: Arc<RwLock<Rate>>) -> Result<()> {
+        let (ws_stream, _) = tokio_tungstenite::connect_async(ws_url)
+            .await
+            .map_err(|e| anyhow!("connect to rate feed: {e}"))?;
+        let (_, mut read) = ws_stream.split();
+        while let Some(msg) = read.next().await {
+            let Message::Text(text) = msg.map_err(|e| anyhow!("rate feed stream error: {e}"))?
+            else {
+                continue;
+            };
+            if let Some(sol_usd) = parse_ticker_price(&text) {
+                *inner.write().await = Rate {
+                    sol_usd,
+                    as_of: Instant::now(),
+                };
+            }
+        }
+        Err(anyhow!("rate feed stream ended"))
+    }
+}
+
+#[async_trait]
+impl LatestRate for StreamingRate {
+    async fn latest(&self) -> Rate {
+        *self.inner.read().await
+    }
+}
+
+/// Parses the `price` field out of a Coinbase/Kraken-style ticker message.
+fn parse_ticker_price(text: &str) -> Option<f64> {
+    let value: serde_json::Value = serde_json::from_str(text).ok()?;
+    value.get("price")?.as_str()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn fixed_rate_always_returns_the_same_quote() {
+        let rate = FixedRate::new(150.0);
+        assert_eq!(rate.latest().await.sol_usd, 150.0);
+        assert_eq!(rate.latest().await.sol_usd, 150.0);
+    }
+
+    #[test]
+    fn parses_price_from_ticker_payload() {
+        let msg = r#"{"type":"ticker","product_id":"SOL-USD","price":"172.34"}"#;
+        assert_eq!(parse_ticker_price(msg), Some(172.34));
+    }
+
+    #[test]
+    fn ignores_payloads_without_a_price_field() {
+        let msg = r#"{"type":"subscriptions"}"#;
+        assert_eq!(parse_ticker_price(msg), None);
+    }
+}
@@ -3,7 +3,9 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use crate::execute::Executor;
+use crate::grpc_balance;
 use crate::http_client::HttpClient;
+use crate::rate::{LatestRate, StreamingRate};
 use crate::util::healthz;
 use crate::{
     buyer,
@@ -16,6 +18,7 @@ use actix_web::{get, post};
 use actix_web::{App, Error, HttpResponse, HttpServer};
 use futures_util::StreamExt;
 use jito_searcher_client::get_searcher_client;
+use listen_kit::data::{self, Candlestick};
 use log::{info, warn};
 use raydium_library::amm;
 use serde::{Deserialize, Serialize};
@@ -25,6 +28,7 @@ use solana_client::nonblocking::pubsub_client::PubsubClient;
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_client::rpc_config::RpcAccountInfoConfig;
 use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::program_pack::Pack;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signer::{EncodableKey, Signer};
@@ -52,11 +56,15 @@ pub struct SellRequest {
 }
 
 #[post("/sell")]
-async fn handle_sell(sell_request: Json<SellRequest>) -> Result<HttpResponse, Error> {
+async fn handle_sell(
+    sell_request: Json<SellRequest>,
+    rate: web::Data<Arc<dyn LatestRate>>,
+) -> Result<HttpResponse, Error> {
     info!(
         "handling sell_request {}",
         serde_json::to_string_pretty(&sell_request)?
     );
+    let rate = rate.get_ref().clone();
     actix_rt::spawn(async move {
         let wallet = Keypair::read_from_file(env("FUND_KEYPAIR_PATH")).expect("read wallet");
         let provider = Provider::new(env("RPC_URL"));
@@ -101,6 +109,23 @@ async fn handle_sell(sell_request: Json<SellRequest>) -> Result<HttpResponse, Er
                 tp_levels: vec![1.5, 2.0, 3.0, 5.0, 10.0],
                 tp_amounts: vec![0.4, 0.2, 0.2, 0.2, 0.2].iter().map(|x| *x * balance as f64).collect(),
                 tp_reached: vec![false, false, false, false, false],
+
+                activation_ratio: 1.5,
+                trail_pct: 0.2,
+
+                // Sell half at +$5k PnL regardless of ratio, and bail
+                // entirely if the position is down $2k, same idea as the
+                // ratio-based sl/tp above but denominated in USD.
+                usd_tp_levels: vec![5000.0],
+                usd_tp_amounts: vec![0.5 * balance as f64],
+                usd_tp_reached: vec![false],
+
+                usd_sl_levels: vec![-2000.0],
+                usd_sl_amounts: vec![balance as f64],
+                usd_sl_reached: vec![false],
+
+                rate,
+                rate_staleness: std::time::Duration::from_secs(30),
             };
             executor
                 .execute(&provider, &pubsub_client, &sell_request.amm_pool)
@@ -199,16 +224,184 @@ impl BalanceContext {
         unsub().await;
     }
 
-    pub async fn track_token_balance(&self, mint: &Pubkey, owner: &Pubkey) {}
+    pub async fn track_token_balance(&self, mint: &Pubkey, owner: &Pubkey) {
+        let ata = spl_associated_token_account::get_associated_token_address(owner, mint);
+        let rpc_client = RpcClient::new(env("RPC_URL"));
+        let decimals = rpc_client
+            .get_token_supply(mint)
+            .await
+            .map(|supply| supply.decimals)
+            .unwrap_or(9);
+
+        let pubsub_client = PubsubClient::new(&env("WS_URL"))
+            .await
+            .expect("make pubsub client");
+        let (mut stream, unsub) = pubsub_client
+            .account_subscribe(
+                &ata,
+                Some(RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .expect("account_subscribe");
+
+        while let Some(log) = stream.next().await {
+            let Some(account) = log.value.data.decode() else {
+                continue;
+            };
+            let Ok(token_account) = spl_token::state::Account::unpack(&account.data) else {
+                continue;
+            };
+            let ui_amount = token_account.amount as f64 / 10f64.powi(decimals as i32);
+            self.token_balances
+                .write()
+                .await
+                .insert(mint.to_string(), ui_amount);
+        }
+        unsub().await;
+    }
+}
+
+/// Which source feeds `BalanceContext`: the original single-account websocket
+/// subscription, or the lower-latency Yellowstone/Geyser gRPC stream.
+/// Selected via the `BALANCE_BACKEND` env var (`pubsub` | `grpc`), defaulting
+/// to `pubsub` to match prior behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceBackend {
+    Pubsub,
+    Grpc,
+}
+
+impl BalanceBackend {
+    fn from_env() -> Self {
+        match std::env::var("BALANCE_BACKEND").as_deref() {
+            Ok("grpc") => BalanceBackend::Grpc,
+            _ => BalanceBackend::Pubsub,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BalanceQuery {
+    pub include_tokens: Option<bool>,
 }
 
 #[get("/balance")]
 pub async fn handle_balance(
     balance_ctx: web::Data<Arc<BalanceContext>>,
+    query: web::Query<BalanceQuery>,
 ) -> Result<HttpResponse, Error> {
     info!("handling balance request");
     let balance = *balance_ctx.lamports.read().await;
-    Ok(HttpResponse::Ok().json(json!({"balance": balance})))
+    if query.include_tokens.unwrap_or(false) {
+        let token_balances = balance_ctx.token_balances.read().await.clone();
+        Ok(HttpResponse::Ok().json(json!({"balance": balance, "token_balances": token_balances})))
+    } else {
+        Ok(HttpResponse::Ok().json(json!({"balance": balance})))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CandlesQuery {
+    pub mint: String,
+    pub interval: String,
+    pub limit: Option<usize>,
+}
+
+#[get("/candles")]
+async fn handle_candles(query: web::Query<CandlesQuery>) -> Result<HttpResponse, Error> {
+    info!("handling candles request for mint {}", query.mint);
+    let candles = data::local_candle_store()
+        .candlesticks(&query.mint, &query.interval, query.limit.unwrap_or(200))
+        .await
+        .map_err(actix_web::error::ErrorBadRequest)?;
+    Ok(HttpResponse::Ok().json(json!({ "candles": candles })))
+}
+
+/// CoinGecko `tickers` endpoint shape: one entry per pool with last price,
+/// 24h volume and price change, assembled from the last two daily candles:
+/// volume is the latest candle's, and the change is the latest close against
+/// the prior candle's open.
+#[derive(Serialize)]
+struct Ticker {
+    ticker_id: String,
+    base_currency: String,
+    target_currency: String,
+    last_price: f64,
+    base_volume: f64,
+    target_volume: f64,
+    pool_id: String,
+    price_change_percent_24h: f64,
+}
+
+fn ticker_from_daily_candles(mint: &str, daily: &[Candlestick]) -> Option<Ticker> {
+    let latest = daily.last()?;
+    let price_change_percent_24h = match daily.len() {
+        n if n >= 2 && daily[n - 2].open != 0.0 => {
+            (latest.close - daily[n - 2].open) / daily[n - 2].open * 100.0
+        }
+        _ => 0.0,
+    };
+    Some(Ticker {
+        ticker_id: format!("{mint}_SOL"),
+        base_currency: mint.to_string(),
+        target_currency: "SOL".to_string(),
+        last_price: latest.close,
+        base_volume: latest.volume,
+        target_volume: latest.volume * latest.close,
+        pool_id: mint.to_string(),
+        price_change_percent_24h,
+    })
+}
+
+#[get("/tickers")]
+async fn handle_tickers() -> Result<HttpResponse, Error> {
+    info!("handling tickers request");
+    let store = data::local_candle_store();
+    let mut tickers = vec![];
+    for mint in store.known_mints().await {
+        let Ok(daily) = store.candlesticks(&mint, "1d", 2).await else {
+            continue;
+        };
+        if let Some(ticker) = ticker_from_daily_candles(&mint, &daily) {
+            tickers.push(ticker);
+        }
+    }
+    Ok(HttpResponse::Ok().json(json!({ "tickers": tickers })))
+}
+
+/// Pools to track for the local candle index and token balances, configured
+/// as `mint:pool,mint:pool,...` via `TRACKED_POOLS`. Empty by default, same
+/// as every other env-gated knob in this service.
+fn tracked_pools() -> Vec<(Pubkey, Pubkey)> {
+    std::env::var("TRACKED_POOLS")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let (mint, pool) = entry.split_once(':')?;
+            Some((Pubkey::from_str(mint).ok()?, Pubkey::from_str(pool).ok()?))
+        })
+        .collect()
+}
+
+/// Spawns the pubsub-backed balance trackers for `funder`'s lamports and
+/// each of `mints`' token balances. Used both as the default backend and as
+/// the fallback when the gRPC backend fails to connect or subscribe.
+fn spawn_pubsub_balance_tracking(balance_ctx: Arc<BalanceContext>, funder: Pubkey, mints: Vec<Pubkey>) {
+    let poll = balance_ctx.clone();
+    tokio::spawn(async move {
+        poll.track_lamports_balance(&funder).await;
+    });
+    for mint in mints {
+        let poll = balance_ctx.clone();
+        tokio::spawn(async move {
+            poll.track_token_balance(&mint, &funder).await;
+        });
+    }
 }
 
 pub async fn run_seller_service() -> std::io::Result<()> {
@@ -233,20 +426,129 @@ pub async fn run_seller_service() -> std::io::Result<()> {
         .await
         .expect("makes searcher client");
 
-    let poll = balance_ctx.clone();
-    tokio::spawn(async move {
-        poll.track_lamports_balance(&wallet.pubkey()).await;
-    });
+    let rate: Arc<dyn LatestRate> = Arc::new(StreamingRate::spawn(env("RATE_WS_URL")));
+
+    let pools = tracked_pools();
+    let mints: Vec<Pubkey> = pools.iter().map(|(mint, _)| *mint).collect();
+
+    match BalanceBackend::from_env() {
+        BalanceBackend::Grpc => {
+            let ctx = balance_ctx.clone();
+            let funder = wallet.pubkey();
+            let mints = mints.clone();
+            tokio::spawn(async move {
+                if let Err(e) =
+                    grpc_balance::track_balances_grpc(ctx.clone(), funder, mints.clone()).await
+                {
+                    warn!("grpc balance tracking failed, falling back to pubsub: {e}");
+                    spawn_pubsub_balance_tracking(ctx, funder, mints);
+                }
+            });
+        }
+        BalanceBackend::Pubsub => {
+            spawn_pubsub_balance_tracking(balance_ctx.clone(), wallet.pubkey(), mints.clone());
+        }
+    }
+
+    for (mint, pool) in pools {
+        tokio::spawn(async move {
+            let provider = Provider::new(env("RPC_URL"));
+            let pubsub_client = match PubsubClient::new(&env("WS_URL")).await {
+                Ok(client) => client,
+                Err(e) => {
+                    warn!("candle indexer: failed to open pubsub client for {pool}: {e}");
+                    return;
+                }
+            };
+            let amm_program = match Pubkey::from_str(constants::RAYDIUM_LIQUIDITY_POOL_V4_PUBKEY)
+            {
+                Ok(program) => program,
+                Err(e) => {
+                    warn!("candle indexer: bad amm program id: {e}");
+                    return;
+                }
+            };
+            let amm_keys =
+                match amm::utils::load_amm_keys(&provider.rpc_client, &amm_program, &pool).await {
+                    Ok(keys) => keys,
+                    Err(e) => {
+                        warn!("candle indexer: failed to load amm keys for {pool}: {e}");
+                        return;
+                    }
+                };
+            if let Err(e) = data::index_pool_swaps(
+                &provider.rpc_client,
+                &pubsub_client,
+                &amm_keys,
+                &mint.to_string(),
+                data::local_candle_store().clone(),
+            )
+            .await
+            {
+                warn!("candle indexer for {pool} ended: {e}");
+            }
+        });
+    }
+
     HttpServer::new(move || {
         App::new()
             .service(handle_sell)
             .service(handle_sell_simple)
             .service(handle_balance)
+            .service(handle_candles)
+            .service(handle_tickers)
             .app_data(web::Data::new(balance_ctx.clone()))
             .app_data(web::Data::new(searcher_client.clone()))
+            .app_data(web::Data::new(rate.clone()))
             .service(healthz)
     })
     .bind(("0.0.0.0", 8081))?
     .run()
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(open: f64, close: f64, volume: f64) -> Candlestick {
+        Candlestick {
+            timestamp: 0,
+            open,
+            high: open.max(close),
+            low: open.min(close),
+            close,
+            volume,
+        }
+    }
+
+    #[test]
+    fn ticker_from_daily_candles_is_none_without_candles() {
+        assert!(ticker_from_daily_candles("mint1", &[]).is_none());
+    }
+
+    #[test]
+    fn ticker_from_daily_candles_with_one_candle_has_no_24h_change() {
+        let daily = [candle(1.0, 1.2, 100.0)];
+        let ticker = ticker_from_daily_candles("mint1", &daily).unwrap();
+        assert_eq!(ticker.last_price, 1.2);
+        assert_eq!(ticker.base_volume, 100.0);
+        assert_eq!(ticker.price_change_percent_24h, 0.0);
+    }
+
+    #[test]
+    fn ticker_from_daily_candles_computes_24h_change_from_prior_open() {
+        let daily = [candle(1.0, 1.1, 50.0), candle(1.1, 1.21, 75.0)];
+        let ticker = ticker_from_daily_candles("mint1", &daily).unwrap();
+        assert_eq!(ticker.last_price, 1.21);
+        assert_eq!(ticker.base_volume, 75.0);
+        assert!((ticker.price_change_percent_24h - 21.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ticker_from_daily_candles_guards_against_zero_prior_open() {
+        let daily = [candle(0.0, 0.5, 10.0), candle(0.5, 0.6, 20.0)];
+        let ticker = ticker_from_daily_candles("mint1", &daily).unwrap();
+        assert_eq!(ticker.price_change_percent_24h, 0.0);
+    }
+}